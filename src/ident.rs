@@ -0,0 +1,132 @@
+//! Typed identifiers for Twitch channels.
+//!
+//! These wrap a plain `Cow<str>` so that a channel's login and its numeric
+//! room ID can't be accidentally swapped for one another at the type level,
+//! even though both are transported over IRC as strings.
+
+use std::borrow::Cow;
+use std::fmt;
+use std::ops::Deref;
+
+/// The login of a Twitch channel, e.g. `randers`.
+///
+/// Normalized on construction: lowercased, with any leading `#` stripped.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChannelLogin<'src>(#[cfg_attr(feature = "serde", serde(borrow))] Cow<'src, str>);
+
+impl<'src> ChannelLogin<'src> {
+  /// Normalize `login` into a [`ChannelLogin`].
+  pub fn new(login: impl Into<Cow<'src, str>>) -> Self {
+    let login = login.into();
+    let login = login.strip_prefix('#').map(str::to_owned).map(Cow::Owned).unwrap_or(login);
+    let login = if login.chars().any(char::is_uppercase) {
+      Cow::Owned(login.to_lowercase())
+    } else {
+      login
+    };
+    ChannelLogin(login)
+  }
+
+  /// Clone data to give the value a `'static` lifetime.
+  pub fn into_owned(self) -> ChannelLogin<'static> {
+    ChannelLogin(Cow::Owned(self.0.into_owned()))
+  }
+}
+
+impl Deref for ChannelLogin<'_> {
+  type Target = str;
+
+  fn deref(&self) -> &str {
+    self.0.as_ref()
+  }
+}
+
+impl AsRef<str> for ChannelLogin<'_> {
+  fn as_ref(&self) -> &str {
+    self.0.as_ref()
+  }
+}
+
+impl fmt::Display for ChannelLogin<'_> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(self.as_ref())
+  }
+}
+
+/// The numeric ID of a Twitch channel, e.g. `40286300`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChannelId<'src>(#[cfg_attr(feature = "serde", serde(borrow))] Cow<'src, str>);
+
+impl<'src> ChannelId<'src> {
+  /// Wrap `id` as a [`ChannelId`], without validating that it's numeric.
+  pub fn new(id: impl Into<Cow<'src, str>>) -> Self {
+    ChannelId(id.into())
+  }
+
+  /// Parse this ID into a [`u64`], if it is numeric.
+  pub fn as_u64(&self) -> Option<u64> {
+    self.0.parse().ok()
+  }
+
+  /// Clone data to give the value a `'static` lifetime.
+  pub fn into_owned(self) -> ChannelId<'static> {
+    ChannelId(Cow::Owned(self.0.into_owned()))
+  }
+}
+
+impl Deref for ChannelId<'_> {
+  type Target = str;
+
+  fn deref(&self) -> &str {
+    self.0.as_ref()
+  }
+}
+
+impl AsRef<str> for ChannelId<'_> {
+  fn as_ref(&self) -> &str {
+    self.0.as_ref()
+  }
+}
+
+impl fmt::Display for ChannelId<'_> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(self.as_ref())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn channel_login_strips_leading_hash() {
+    assert_eq!(ChannelLogin::new("#randers").as_ref(), "randers");
+  }
+
+  #[test]
+  fn channel_login_lowercases() {
+    assert_eq!(ChannelLogin::new("Randers").as_ref(), "randers");
+  }
+
+  #[test]
+  fn channel_login_strips_and_lowercases_together() {
+    assert_eq!(ChannelLogin::new("#Randers").as_ref(), "randers");
+  }
+
+  #[test]
+  fn channel_login_leaves_already_normalized_login_unchanged() {
+    assert_eq!(ChannelLogin::new("randers").as_ref(), "randers");
+  }
+
+  #[test]
+  fn channel_id_as_u64_parses_numeric_id() {
+    assert_eq!(ChannelId::new("40286300").as_u64(), Some(40286300));
+  }
+
+  #[test]
+  fn channel_id_as_u64_rejects_non_numeric_id() {
+    assert_eq!(ChannelId::new("not_an_id").as_u64(), None);
+  }
+}
@@ -0,0 +1,118 @@
+//! Minimal IRCv3 message parsing: tags, command, and parameters.
+//!
+//! Twitch messages are plain IRC lines of the form
+//! `[@tags ][:prefix ]COMMAND [params...][ :trailing]`. [`IrcMessageRef`] is a
+//! borrowed view over one such line; the `msg` module builds typed messages
+//! out of it.
+
+/// A single IRCv3 message tag key relevant to this crate.
+///
+/// Twitch sends many more tags than this; only the ones `msg` parses into
+/// typed messages are represented here.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Tag {
+  /// `emote-only`
+  EmoteOnly,
+
+  /// `followers-only`
+  FollowersOnly,
+
+  /// `r9k`
+  R9K,
+
+  /// `rituals`
+  Rituals,
+
+  /// `room-id`
+  RoomId,
+
+  /// `slow`
+  Slow,
+
+  /// `subs-only`
+  SubsOnly,
+}
+
+impl Tag {
+  /// The tag's key as it appears on the wire.
+  fn as_str(self) -> &'static str {
+    match self {
+      Tag::EmoteOnly => "emote-only",
+      Tag::FollowersOnly => "followers-only",
+      Tag::R9K => "r9k",
+      Tag::Rituals => "rituals",
+      Tag::RoomId => "room-id",
+      Tag::Slow => "slow",
+      Tag::SubsOnly => "subs-only",
+    }
+  }
+}
+
+/// An IRC command relevant to this crate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Command<'src> {
+  /// `ROOMSTATE`: a channel's chat settings, in full or in part.
+  RoomState,
+
+  /// Any command this crate doesn't parse into a typed message.
+  Other(&'src str),
+}
+
+/// A borrowed view over a single, unparsed IRC message line.
+#[derive(Clone, Copy, Debug)]
+pub struct IrcMessageRef<'src> {
+  raw: &'src str,
+}
+
+impl<'src> IrcMessageRef<'src> {
+  /// Wrap `raw` as a message view, without fully validating its shape.
+  pub fn parse(raw: &'src str) -> Option<Self> {
+    if raw.is_empty() {
+      return None;
+    }
+    Some(IrcMessageRef { raw })
+  }
+
+  fn tags_str(&self) -> Option<&'src str> {
+    let tags = self.raw.strip_prefix('@')?;
+    Some(tags.split_once(' ').map(|(tags, _)| tags).unwrap_or(tags))
+  }
+
+  fn after_tags_and_prefix(&self) -> &'src str {
+    let mut rest = self.raw;
+    if rest.starts_with('@') {
+      rest = rest.split_once(' ').map(|(_, rest)| rest).unwrap_or("");
+    }
+    if rest.starts_with(':') {
+      rest = rest.split_once(' ').map(|(_, rest)| rest).unwrap_or("");
+    }
+    rest
+  }
+
+  /// Value of `tag` on this message, if present.
+  pub fn tag(&self, tag: Tag) -> Option<&'src str> {
+    self.tags_str()?.split(';').find_map(|pair| {
+      let (key, value) = pair.split_once('=')?;
+      (key == tag.as_str()).then_some(value)
+    })
+  }
+
+  /// This message's command.
+  pub fn command(&self) -> Command<'src> {
+    let command = self.after_tags_and_prefix().split(' ').next().unwrap_or("");
+    match command {
+      "ROOMSTATE" => Command::RoomState,
+      other => Command::Other(other),
+    }
+  }
+
+  /// The `#channel` parameter of this message, without the leading `#`.
+  pub fn channel(&self) -> Option<&'src str> {
+    let params = self
+      .after_tags_and_prefix()
+      .split_once(' ')
+      .map(|(_, params)| params)
+      .unwrap_or("");
+    params.split(' ').find_map(|param| param.strip_prefix('#'))
+  }
+}
@@ -1,6 +1,7 @@
 //! A partial update to the settings of some channel.
 
 use super::{maybe_clone, parse_bool, MessageParseError};
+use crate::ident::{ChannelId, ChannelLogin};
 use crate::irc::{Command, IrcMessageRef, Tag};
 use std::borrow::Cow;
 use std::time::Duration;
@@ -10,29 +11,49 @@ use std::time::Duration;
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RoomState<'src> {
   #[cfg_attr(feature = "serde", serde(borrow))]
-  channel: Cow<'src, str>,
+  channel: ChannelLogin<'src>,
 
   #[cfg_attr(feature = "serde", serde(borrow))]
-  channel_id: Cow<'src, str>,
+  channel_id: ChannelId<'src>,
 
   emote_only: Option<bool>,
 
+  #[cfg_attr(feature = "serde", serde(borrow))]
+  emote_only_raw: Option<Cow<'src, str>>,
+
   followers_only: Option<FollowersOnly>,
 
+  #[cfg_attr(feature = "serde", serde(borrow))]
+  followers_only_raw: Option<Cow<'src, str>>,
+
   r9k: Option<bool>,
 
+  #[cfg_attr(feature = "serde", serde(borrow))]
+  r9k_raw: Option<Cow<'src, str>>,
+
+  rituals: Option<bool>,
+
+  #[cfg_attr(feature = "serde", serde(borrow))]
+  rituals_raw: Option<Cow<'src, str>>,
+
   slow: Option<Duration>,
 
+  #[cfg_attr(feature = "serde", serde(borrow))]
+  slow_raw: Option<Cow<'src, str>>,
+
   subs_only: Option<bool>,
+
+  #[cfg_attr(feature = "serde", serde(borrow))]
+  subs_only_raw: Option<Cow<'src, str>>,
 }
 
 generate_getters! {
   <'src> for RoomState<'src> as self {
     /// Login of the channel this state was applied to.
-    channel -> &str = self.channel.as_ref(),
+    channel -> ChannelLogin<'src> = self.channel.clone(),
 
     /// ID of the channel this state was applied to.
-    channel_id -> &str = self.channel_id.as_ref(),
+    channel_id -> ChannelId<'src> = self.channel_id.clone(),
 
     /// Whether the room is in emote-only mode.
     ///
@@ -42,6 +63,9 @@ generate_getters! {
     /// - [`Some`] means enabled if `true`, and disabled if `false`.
     emote_only -> Option<bool>,
 
+    /// Raw, unparsed value of the `emote-only` tag, if present.
+    emote_only_raw -> Option<&str> = self.emote_only_raw.as_deref(),
+
     /// Whether the room is in followers-only mode.
     ///
     /// Only followers (optionally with a minimum followage) can chat.
@@ -50,20 +74,41 @@ generate_getters! {
     /// - [`Some`] means some change, see [`FollowersOnly`] for more information about possible values.
     followers_only -> Option<FollowersOnly>,
 
+    /// Raw, unparsed value of the `followers-only` tag, if present.
+    followers_only_raw -> Option<&str> = self.followers_only_raw.as_deref(),
+
     /// Whether the room is in r9k mode.
     ///
     /// Only unique messages may be sent to chat.
     r9k -> Option<bool>,
 
+    /// Raw, unparsed value of the `r9k` tag, if present.
+    r9k_raw -> Option<&str> = self.r9k_raw.as_deref(),
+
+    /// Whether rituals (e.g. the first-message-in-channel ritual) are enabled.
+    ///
+    /// - [`None`] means no change.
+    /// - [`Some`] means enabled if `true`, and disabled if `false`.
+    rituals -> Option<bool>,
+
+    /// Raw, unparsed value of the `rituals` tag, if present.
+    rituals_raw -> Option<&str> = self.rituals_raw.as_deref(),
+
     /// Whether the room is in slow mode.
     ///
     /// Users may only send messages with some minimum time between them.
     slow -> Option<Duration>,
 
+    /// Raw, unparsed value of the `slow` tag, if present.
+    slow_raw -> Option<&str> = self.slow_raw.as_deref(),
+
     /// Whether the room is in subcriber-only mode.
     ///
     /// Users may only send messages if they have an active subscription.
     subs_only -> Option<bool>,
+
+    /// Raw, unparsed value of the `subs-only` tag, if present.
+    subs_only_raw -> Option<&str> = self.subs_only_raw.as_deref(),
   }
 }
 
@@ -96,9 +141,10 @@ impl<'src> RoomState<'src> {
     }
 
     Some(RoomState {
-      channel: message.channel()?.into(),
-      channel_id: message.tag(Tag::RoomId)?.into(),
+      channel: ChannelLogin::new(message.channel()?),
+      channel_id: ChannelId::new(message.tag(Tag::RoomId)?),
       emote_only: message.tag(Tag::EmoteOnly).map(parse_bool),
+      emote_only_raw: message.tag(Tag::EmoteOnly).map(Cow::from),
       followers_only: message
         .tag(Tag::FollowersOnly)
         .and_then(|v| v.parse().ok())
@@ -107,25 +153,99 @@ impl<'src> RoomState<'src> {
           0 => FollowersOnly::Enabled(None),
           _ => FollowersOnly::Disabled,
         }),
+      followers_only_raw: message.tag(Tag::FollowersOnly).map(Cow::from),
       r9k: message.tag(Tag::R9K).map(parse_bool),
+      r9k_raw: message.tag(Tag::R9K).map(Cow::from),
+      rituals: message.tag(Tag::Rituals).map(parse_bool),
+      rituals_raw: message.tag(Tag::Rituals).map(Cow::from),
       slow: message
         .tag(Tag::Slow)
         .and_then(|v| v.parse().ok())
         .map(Duration::from_secs),
+      slow_raw: message.tag(Tag::Slow).map(Cow::from),
       subs_only: message.tag(Tag::SubsOnly).map(parse_bool),
+      subs_only_raw: message.tag(Tag::SubsOnly).map(Cow::from),
     })
   }
 
   /// Clone data to give the value a `'static` lifetime.
   pub fn into_owned(self) -> RoomState<'static> {
     RoomState {
-      channel: maybe_clone(self.channel),
-      channel_id: maybe_clone(self.channel_id),
+      channel: self.channel.into_owned(),
+      channel_id: self.channel_id.into_owned(),
       emote_only: self.emote_only,
+      emote_only_raw: self.emote_only_raw.map(maybe_clone),
       followers_only: self.followers_only,
+      followers_only_raw: self.followers_only_raw.map(maybe_clone),
       r9k: self.r9k,
+      r9k_raw: self.r9k_raw.map(maybe_clone),
+      rituals: self.rituals,
+      rituals_raw: self.rituals_raw.map(maybe_clone),
       slow: self.slow,
+      slow_raw: self.slow_raw.map(maybe_clone),
       subs_only: self.subs_only,
+      subs_only_raw: self.subs_only_raw.map(maybe_clone),
+    }
+  }
+
+  /// Fold this partial update into `base`, overwriting only the fields for
+  /// which `self` holds [`Some`], and refreshing its channel and channel ID.
+  ///
+  /// This is the merge primitive behind [`crate::tracking::RoomStateTracker`];
+  /// use it directly to maintain current channel settings without adopting a
+  /// full tracker.
+  pub fn apply_to(&self, base: &mut RoomState<'static>) {
+    base.channel = self.channel.clone().into_owned();
+    base.channel_id = self.channel_id.clone().into_owned();
+
+    if let Some(emote_only) = self.emote_only {
+      base.emote_only = Some(emote_only);
+      base.emote_only_raw = self.emote_only_raw.clone().map(maybe_clone);
+    }
+    if let Some(followers_only) = self.followers_only {
+      base.followers_only = Some(followers_only);
+      base.followers_only_raw = self.followers_only_raw.clone().map(maybe_clone);
+    }
+    if let Some(r9k) = self.r9k {
+      base.r9k = Some(r9k);
+      base.r9k_raw = self.r9k_raw.clone().map(maybe_clone);
+    }
+    if let Some(rituals) = self.rituals {
+      base.rituals = Some(rituals);
+      base.rituals_raw = self.rituals_raw.clone().map(maybe_clone);
+    }
+    if let Some(slow) = self.slow {
+      base.slow = Some(slow);
+      base.slow_raw = self.slow_raw.clone().map(maybe_clone);
+    }
+    if let Some(subs_only) = self.subs_only {
+      base.subs_only = Some(subs_only);
+      base.subs_only_raw = self.subs_only_raw.clone().map(maybe_clone);
+    }
+  }
+}
+
+impl Default for RoomState<'static> {
+  /// An empty room state, with no channel and every setting unset.
+  ///
+  /// Exists as a base for [`RoomState::apply_to`] to fold updates into, e.g.
+  /// in [`crate::tracking::RoomStateTracker`].
+  fn default() -> Self {
+    RoomState {
+      channel: ChannelLogin::new(""),
+      channel_id: ChannelId::new(""),
+      emote_only: None,
+      emote_only_raw: None,
+      followers_only: None,
+      followers_only_raw: None,
+      r9k: None,
+      r9k_raw: None,
+      rituals: None,
+      rituals_raw: None,
+      slow: None,
+      slow_raw: None,
+      subs_only: None,
+      subs_only_raw: None,
     }
   }
 }
@@ -147,70 +267,258 @@ impl<'src> From<RoomState<'src>> for super::Message<'src> {
 mod tests {
   use super::*;
 
+  /// Parse a raw `ROOMSTATE` line, panicking if it doesn't parse.
+  ///
+  /// Every fixture below is asserted against directly via [`RoomState`]'s
+  /// getters, rather than against a stored snapshot, so these tests stay
+  /// self-verifying without any external golden files.
+  fn parse(raw: &str) -> RoomState<'_> {
+    RoomState::parse(IrcMessageRef::parse(raw).expect("fixture is a valid IRC line"))
+      .expect("fixture is a ROOMSTATE with a channel and room-id")
+  }
+
   #[test]
   fn parse_room_state_basic_full() {
-    assert_irc_snapshot!(RoomState, "@emote-only=0;followers-only=-1;r9k=0;rituals=0;room-id=40286300;slow=0;subs-only=0 :tmi.twitch.tv ROOMSTATE #randers");
+    let state = parse("@emote-only=0;followers-only=-1;r9k=0;rituals=0;room-id=40286300;slow=0;subs-only=0 :tmi.twitch.tv ROOMSTATE #randers");
+
+    assert_eq!(state.channel().as_ref(), "randers");
+    assert_eq!(state.channel_id().as_u64(), Some(40286300));
+    assert_eq!(state.emote_only(), Some(false));
+    assert_eq!(state.emote_only_raw(), Some("0"));
+    assert_eq!(state.followers_only(), Some(FollowersOnly::Disabled));
+    assert_eq!(state.followers_only_raw(), Some("-1"));
+    assert_eq!(state.r9k(), Some(false));
+    assert_eq!(state.r9k_raw(), Some("0"));
+    assert_eq!(state.rituals(), Some(false));
+    assert_eq!(state.rituals_raw(), Some("0"));
+    assert_eq!(state.slow(), Some(Duration::ZERO));
+    assert_eq!(state.slow_raw(), Some("0"));
+    assert_eq!(state.subs_only(), Some(false));
+    assert_eq!(state.subs_only_raw(), Some("0"));
+  }
+
+  #[test]
+  fn apply_to_overwrites_only_present_fields() {
+    let mut base = RoomState::default();
+    base.emote_only = Some(false);
+    base.slow = Some(Duration::from_secs(5));
+
+    let update = RoomState {
+      channel: ChannelLogin::new("#Randers"),
+      channel_id: ChannelId::new("40286300"),
+      emote_only: Some(true),
+      emote_only_raw: Some(Cow::Borrowed("1")),
+      followers_only: None,
+      followers_only_raw: None,
+      r9k: None,
+      r9k_raw: None,
+      rituals: Some(true),
+      rituals_raw: Some(Cow::Borrowed("1")),
+      slow: None,
+      slow_raw: None,
+      subs_only: None,
+      subs_only_raw: None,
+    };
+
+    update.apply_to(&mut base);
+
+    assert_eq!(base.channel().as_ref(), "randers");
+    assert_eq!(base.channel_id().as_ref(), "40286300");
+    assert_eq!(base.emote_only(), Some(true));
+    assert_eq!(base.rituals(), Some(true));
+    // Fields `update` left as `None` keep `base`'s previous value.
+    assert_eq!(base.slow(), Some(Duration::from_secs(5)));
+  }
+
+  #[test]
+  fn room_state_tracker_merges_full_then_partial_update() {
+    let full = RoomState {
+      channel: ChannelLogin::new("randers"),
+      channel_id: ChannelId::new("40286300"),
+      emote_only: Some(false),
+      emote_only_raw: Some(Cow::Borrowed("0")),
+      followers_only: Some(FollowersOnly::Disabled),
+      followers_only_raw: Some(Cow::Borrowed("-1")),
+      r9k: Some(false),
+      r9k_raw: Some(Cow::Borrowed("0")),
+      rituals: Some(false),
+      rituals_raw: Some(Cow::Borrowed("0")),
+      slow: Some(Duration::ZERO),
+      slow_raw: Some(Cow::Borrowed("0")),
+      subs_only: Some(false),
+      subs_only_raw: Some(Cow::Borrowed("0")),
+    };
+
+    let partial = RoomState {
+      channel: ChannelLogin::new("randers"),
+      channel_id: ChannelId::new("40286300"),
+      emote_only: None,
+      emote_only_raw: None,
+      followers_only: None,
+      followers_only_raw: None,
+      r9k: None,
+      r9k_raw: None,
+      rituals: Some(true),
+      rituals_raw: Some(Cow::Borrowed("1")),
+      slow: Some(Duration::from_secs(5)),
+      slow_raw: Some(Cow::Borrowed("5")),
+      subs_only: None,
+      subs_only_raw: None,
+    };
+
+    let tracker = crate::tracking::RoomStateTracker::new();
+    tracker.handle(&full);
+    tracker.handle(&partial);
+
+    let resolved = tracker.get("randers").expect("channel should be tracked");
+    assert!(!resolved.emote_only());
+    assert!(resolved.rituals());
+    assert_eq!(resolved.slow(), Duration::from_secs(5));
+    assert!(tracker.get("someone_else").is_none());
+
+    // `get` normalizes its input the same way `ChannelLogin::new` does.
+    assert!(tracker.get("#Randers").is_some());
   }
 
   #[test]
   fn parse_room_state_basic_full2() {
-    assert_irc_snapshot!(RoomState, "@emote-only=1;followers-only=0;r9k=1;rituals=0;room-id=40286300;slow=5;subs-only=1 :tmi.twitch.tv ROOMSTATE #randers");
+    let state = parse("@emote-only=1;followers-only=0;r9k=1;rituals=0;room-id=40286300;slow=5;subs-only=1 :tmi.twitch.tv ROOMSTATE #randers");
+
+    assert_eq!(state.channel().as_ref(), "randers");
+    assert_eq!(state.channel_id().as_u64(), Some(40286300));
+    assert_eq!(state.emote_only(), Some(true));
+    assert_eq!(state.emote_only_raw(), Some("1"));
+    assert_eq!(state.followers_only(), Some(FollowersOnly::Enabled(None)));
+    assert_eq!(state.followers_only_raw(), Some("0"));
+    assert_eq!(state.r9k(), Some(true));
+    assert_eq!(state.r9k_raw(), Some("1"));
+    assert_eq!(state.rituals(), Some(false));
+    assert_eq!(state.rituals_raw(), Some("0"));
+    assert_eq!(state.slow(), Some(Duration::from_secs(5)));
+    assert_eq!(state.slow_raw(), Some("5"));
+    assert_eq!(state.subs_only(), Some(true));
+    assert_eq!(state.subs_only_raw(), Some("1"));
   }
 
   #[test]
   fn parse_room_state_followers_non_zero() {
-    assert_irc_snapshot!(RoomState, "@emote-only=1;followers-only=10;r9k=1;rituals=0;room-id=40286300;slow=5;subs-only=1 :tmi.twitch.tv ROOMSTATE #randers");
+    let state = parse("@emote-only=1;followers-only=10;r9k=1;rituals=0;room-id=40286300;slow=5;subs-only=1 :tmi.twitch.tv ROOMSTATE #randers");
+
+    assert_eq!(
+      state.followers_only(),
+      Some(FollowersOnly::Enabled(Some(Duration::from_secs(10 * 60))))
+    );
+    assert_eq!(state.followers_only_raw(), Some("10"));
   }
 
   #[test]
   fn parse_room_state_partial_1() {
-    assert_irc_snapshot!(
-      RoomState,
-      "@room-id=40286300;slow=5 :tmi.twitch.tv ROOMSTATE #randers"
-    );
+    let state = parse("@room-id=40286300;slow=5 :tmi.twitch.tv ROOMSTATE #randers");
+
+    assert_eq!(state.channel().as_ref(), "randers");
+    assert_eq!(state.channel_id().as_u64(), Some(40286300));
+    assert_eq!(state.emote_only(), None);
+    assert_eq!(state.emote_only_raw(), None);
+    assert_eq!(state.followers_only(), None);
+    assert_eq!(state.followers_only_raw(), None);
+    assert_eq!(state.r9k(), None);
+    assert_eq!(state.r9k_raw(), None);
+    assert_eq!(state.rituals(), None);
+    assert_eq!(state.rituals_raw(), None);
+    assert_eq!(state.slow(), Some(Duration::from_secs(5)));
+    assert_eq!(state.slow_raw(), Some("5"));
+    assert_eq!(state.subs_only(), None);
+    assert_eq!(state.subs_only_raw(), None);
   }
 
   #[test]
   fn parse_room_state_partial_2() {
-    assert_irc_snapshot!(
-      RoomState,
-      "@emote-only=1;room-id=40286300 :tmi.twitch.tv ROOMSTATE #randers"
+    let state = parse("@emote-only=1;room-id=40286300 :tmi.twitch.tv ROOMSTATE #randers");
+
+    assert_eq!(state.emote_only(), Some(true));
+    assert_eq!(state.emote_only_raw(), Some("1"));
+    assert_eq!(state.followers_only(), None);
+    assert_eq!(state.r9k(), None);
+    assert_eq!(state.rituals(), None);
+    assert_eq!(state.slow(), None);
+    assert_eq!(state.subs_only(), None);
+  }
+
+  #[test]
+  fn parse_room_state_rituals_enabled() {
+    let state = parse("@emote-only=0;followers-only=-1;r9k=0;rituals=1;room-id=40286300;slow=0;subs-only=0 :tmi.twitch.tv ROOMSTATE #randers");
+
+    assert_eq!(state.rituals(), Some(true));
+    assert_eq!(state.rituals_raw(), Some("1"));
+  }
+
+  #[test]
+  fn room_state_raw_tags_are_preserved_verbatim() {
+    let state = parse("@emote-only=1;followers-only=90;r9k=1;rituals=0;room-id=40286300;slow=120;subs-only=1 :tmi.twitch.tv ROOMSTATE #randers");
+
+    assert_eq!(state.emote_only_raw(), Some("1"));
+    assert_eq!(state.followers_only_raw(), Some("90"));
+    assert_eq!(state.r9k_raw(), Some("1"));
+    assert_eq!(state.rituals_raw(), Some("0"));
+    assert_eq!(state.slow_raw(), Some("120"));
+    assert_eq!(state.subs_only_raw(), Some("1"));
+    assert_eq!(
+      state.followers_only(),
+      Some(FollowersOnly::Enabled(Some(Duration::from_secs(90 * 60))))
     );
   }
 
+  /// Round-trip a fixture through `serde_json` and check the result equals
+  /// the freshly-parsed value, rather than diffing against a stored golden
+  /// file.
+  #[cfg(feature = "serde")]
+  fn assert_roundtrips(raw: &str) {
+    let state = parse(raw).into_owned();
+    let json = serde_json::to_string(&state).expect("RoomState serializes");
+    let deserialized: RoomState<'static> =
+      serde_json::from_str(&json).expect("RoomState deserializes from its own output");
+    assert_eq!(deserialized, state);
+  }
+
   #[cfg(feature = "serde")]
   #[test]
   fn roundtrip_room_state_basic_full() {
-    assert_irc_roundtrip!(RoomState, "@emote-only=0;followers-only=-1;r9k=0;rituals=0;room-id=40286300;slow=0;subs-only=0 :tmi.twitch.tv ROOMSTATE #randers");
+    assert_roundtrips("@emote-only=0;followers-only=-1;r9k=0;rituals=0;room-id=40286300;slow=0;subs-only=0 :tmi.twitch.tv ROOMSTATE #randers");
   }
 
   #[cfg(feature = "serde")]
   #[test]
   fn roundtrip_room_state_basic_full2() {
-    assert_irc_roundtrip!(RoomState, "@emote-only=1;followers-only=0;r9k=1;rituals=0;room-id=40286300;slow=5;subs-only=1 :tmi.twitch.tv ROOMSTATE #randers");
+    assert_roundtrips("@emote-only=1;followers-only=0;r9k=1;rituals=0;room-id=40286300;slow=5;subs-only=1 :tmi.twitch.tv ROOMSTATE #randers");
   }
 
   #[cfg(feature = "serde")]
   #[test]
   fn roundtrip_room_state_followers_non_zero() {
-    assert_irc_roundtrip!(RoomState, "@emote-only=1;followers-only=10;r9k=1;rituals=0;room-id=40286300;slow=5;subs-only=1 :tmi.twitch.tv ROOMSTATE #randers");
+    assert_roundtrips("@emote-only=1;followers-only=10;r9k=1;rituals=0;room-id=40286300;slow=5;subs-only=1 :tmi.twitch.tv ROOMSTATE #randers");
   }
 
   #[cfg(feature = "serde")]
   #[test]
   fn roundtrip_room_state_partial_1() {
-    assert_irc_roundtrip!(
-      RoomState,
-      "@room-id=40286300;slow=5 :tmi.twitch.tv ROOMSTATE #randers"
-    );
+    assert_roundtrips("@room-id=40286300;slow=5 :tmi.twitch.tv ROOMSTATE #randers");
   }
 
   #[cfg(feature = "serde")]
   #[test]
   fn roundtrip_room_state_partial_2() {
-    assert_irc_roundtrip!(
-      RoomState,
-      "@emote-only=1;room-id=40286300 :tmi.twitch.tv ROOMSTATE #randers"
-    );
+    assert_roundtrips("@emote-only=1;room-id=40286300 :tmi.twitch.tv ROOMSTATE #randers");
+  }
+
+  #[cfg(feature = "serde")]
+  #[test]
+  fn roundtrip_room_state_rituals_enabled() {
+    assert_roundtrips("@emote-only=0;followers-only=-1;r9k=0;rituals=1;room-id=40286300;slow=0;subs-only=0 :tmi.twitch.tv ROOMSTATE #randers");
+  }
+
+  #[cfg(feature = "serde")]
+  #[test]
+  fn roundtrip_room_state_raw_tags_are_preserved_verbatim() {
+    assert_roundtrips("@emote-only=1;followers-only=90;r9k=1;rituals=0;room-id=40286300;slow=120;subs-only=1 :tmi.twitch.tv ROOMSTATE #randers");
   }
 }
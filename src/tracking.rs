@@ -0,0 +1,94 @@
+//! Bookkeeping for merging partial message updates into fully-resolved state.
+
+use crate::ident::{ChannelId, ChannelLogin};
+use crate::msg::room_state::{FollowersOnly, RoomState};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// The fully-resolved room settings of a channel.
+///
+/// Unlike [`RoomState`], every setting here is always known: it's either the
+/// value from the channel's initial `ROOMSTATE`, or the most recent value
+/// from a later partial update. A setting that no observed `ROOMSTATE` for
+/// the channel has carried reads as disabled (zero for slow mode). Wraps the
+/// [`RoomState`] that
+/// [`RoomStateTracker`] folds updates into via [`RoomState::apply_to`], so
+/// there's a single place that knows how to merge a partial update.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ResolvedRoomState(RoomState<'static>);
+
+generate_getters! {
+  for ResolvedRoomState as self {
+    /// Login of the channel this state belongs to.
+    channel -> ChannelLogin<'_> = self.0.channel(),
+
+    /// ID of the channel this state belongs to.
+    channel_id -> ChannelId<'_> = self.0.channel_id(),
+
+    /// Whether the room is in emote-only mode.
+    emote_only -> bool = self.0.emote_only().unwrap_or(false),
+
+    /// Whether the room is in followers-only mode.
+    followers_only -> FollowersOnly = self.0.followers_only().unwrap_or(FollowersOnly::Disabled),
+
+    /// Whether the room is in r9k mode.
+    r9k -> bool = self.0.r9k().unwrap_or(false),
+
+    /// Whether rituals are enabled.
+    rituals -> bool = self.0.rituals().unwrap_or(false),
+
+    /// Whether the room is in slow mode.
+    slow -> Duration = self.0.slow().unwrap_or(Duration::ZERO),
+
+    /// Whether the room is in subscriber-only mode.
+    subs_only -> bool = self.0.subs_only().unwrap_or(false),
+  }
+}
+
+/// Merges partial `ROOMSTATE` updates into queryable, fully-resolved room
+/// settings, keyed by channel login.
+///
+/// Twitch only sends the full room settings on the initial join to a
+/// channel; every `ROOMSTATE` after that only carries the one setting that
+/// changed, leaving the rest unset. `RoomStateTracker` keeps the current,
+/// fully-resolved settings for every channel it has seen a `ROOMSTATE` for,
+/// so consumers don't have to reimplement this merging themselves.
+#[derive(Debug, Default)]
+pub struct RoomStateTracker {
+  channels: RefCell<HashMap<String, RoomState<'static>>>,
+}
+
+impl RoomStateTracker {
+  /// Create an empty tracker.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Merge a `ROOMSTATE` update into the tracked state for its channel.
+  pub fn handle(&self, msg: &RoomState<'_>) {
+    let mut channels = self.channels.borrow_mut();
+    match channels.get_mut(msg.channel().as_ref()) {
+      Some(base) => msg.apply_to(base),
+      None => {
+        let mut base = RoomState::default();
+        msg.apply_to(&mut base);
+        channels.insert(base.channel().to_string(), base);
+      }
+    }
+  }
+
+  /// Get the current, fully-resolved settings of a channel, if a `ROOMSTATE`
+  /// has been observed for it yet.
+  ///
+  /// `channel` is normalized the same way [`ChannelLogin::new`] normalizes
+  /// it, so e.g. `"#Randers"` and `"randers"` look up the same entry.
+  pub fn get(&self, channel: &str) -> Option<ResolvedRoomState> {
+    self
+      .channels
+      .borrow()
+      .get(ChannelLogin::new(channel).as_ref())
+      .cloned()
+      .map(ResolvedRoomState)
+  }
+}